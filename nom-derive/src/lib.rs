@@ -0,0 +1,208 @@
+//! `#[derive(Parse)]`: turn a struct definition into a nom parser.
+//!
+//! nom already lets a grammar be described as a tree of combinator
+//! macros (`chain!`, `do_parse!`), but for fixed-layout binary or text
+//! formats that tree usually just restates the struct's fields in the
+//! same order. This crate lets the struct do double duty: annotate each
+//! field with the parser that produces it, and `#[derive(Parse)]`
+//! generates a `named!`-equivalent function that runs those parsers in
+//! declaration order and assembles the struct from their outputs.
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate nom;
+//! #[macro_use]
+//! extern crate nom_derive;
+//!
+//! #[derive(Parse)]
+//! struct Point {
+//!   #[parse(tag = "(")]
+//!   open: (),
+//!   #[parse(with = "i64_digit")]
+//!   x: i64,
+//!   #[parse(tag = ",")]
+//!   comma: (),
+//!   #[parse(with = "i64_digit")]
+//!   y: i64,
+//!   #[parse(tag = ")")]
+//!   close: (),
+//! }
+//! ```
+//!
+//! generates a `Point::parse(&[u8]) -> IResult<&[u8], Point>` that runs
+//! each field's parser against the remaining input and threads it
+//! through, exactly the way a hand-written `chain!` would.
+
+extern crate proc_macro;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+use proc_macro::TokenStream;
+use syn::{Body, Ident, VariantData};
+use quote::ToTokens;
+
+#[proc_macro_derive(Parse, attributes(parse))]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_derive_input(&source).expect("#[derive(Parse)] only applies to structs");
+
+  let fields = match ast.body {
+    Body::Struct(VariantData::Struct(ref fields)) => fields,
+    _ => panic!("#[derive(Parse)] only supports structs with named fields"),
+  };
+
+  let name = &ast.ident;
+  let parse_fn = Ident::new(format!("parse_{}", to_snake_case(&name.to_string())));
+
+  let parsed_fields: Vec<_> = fields.iter().map(field_parser).collect();
+
+  // `chain!`'s clauses are joined with `~`, not `,`, and quote 0.3's
+  // `#(..)*` repetition only ever joins with a literal `,` (or nothing);
+  // it can't be told to join with `~`. Build the joined token stream by
+  // hand instead of asking a repetition to do it.
+  let mut bindings = quote::Tokens::new();
+  for (i, field) in parsed_fields.iter().enumerate() {
+    if i > 0 {
+      bindings.append("~");
+    }
+    let parser = &field.parser;
+    if field.captured {
+      let ident = &field.ident;
+      quote! { #ident: #parser }.to_tokens(&mut bindings);
+    } else {
+      // A tag-only field exists purely to sequence a delimiter or
+      // separator; its output (e.g. `tag!("(")`'s matched bytes)
+      // doesn't fit the field's own type (e.g. `()`), so it's
+      // consumed and discarded here, and the struct literal below
+      // sets the field to `()`.
+      quote! { _: #parser }.to_tokens(&mut bindings);
+    }
+  }
+
+  // Likewise, build the struct literal's `field: value` pairs by hand:
+  // repeating the same `captured_names` variable twice inside one
+  // `#(..)*` group isn't valid quote 0.3 - each slot consumes the
+  // iterator, so the second `#captured_names` sees it already drained.
+  //
+  // Every field still needs an entry here, captured or not - a struct
+  // literal has to initialize every field. Tag-only fields are declared
+  // `()`-typed by convention (see the module docs), so `()` is always a
+  // valid value for them regardless of what `tag!` actually matched.
+  let mut struct_fields = quote::Tokens::new();
+  for (i, field) in parsed_fields.iter().enumerate() {
+    if i > 0 {
+      struct_fields.append(",");
+    }
+    let ident = &field.ident;
+    if field.captured {
+      quote! { #ident: #ident }.to_tokens(&mut struct_fields);
+    } else {
+      quote! { #ident: () }.to_tokens(&mut struct_fields);
+    }
+  }
+
+  let expanded = quote! {
+    named!(pub #parse_fn<&[u8], #name>, chain!(
+      #bindings,
+      || {
+        #name {
+          #struct_fields
+        }
+      }
+    ));
+
+    impl #name {
+      /// Parses a `#name` from the front of `input`, in the order its
+      /// fields are declared.
+      pub fn parse(input: &[u8]) -> ::nom::IResult<&[u8], #name> {
+        #parse_fn(input)
+      }
+    }
+  };
+
+  expanded.parse().expect("generated parser failed to parse as Rust source")
+}
+
+/// A struct field together with the combinator its `#[parse(..)]`
+/// attribute describes.
+struct ParsedField {
+  ident: Ident,
+  parser: quote::Tokens,
+  /// Whether this field's parser output is threaded into the struct
+  /// literal. `tag`-only fields exist purely to consume a delimiter or
+  /// separator (their declared type, typically `()`, doesn't match what
+  /// `tag!` returns) so they're sequenced but never captured.
+  captured: bool,
+}
+
+/// Builds the combinator expression for a single field from its
+/// `#[parse(..)]` attribute.
+///
+/// - `#[parse(tag = "...")]` runs `tag!(...)` purely for sequencing; its
+///   output is discarded and the field is set to `()`.
+/// - `#[parse(with = "f")]` runs `call!(f)` and captures its output into
+///   the field.
+/// - Either can be wrapped with `#[parse(many0)]` to run it zero or more
+///   times; for a `with` field the outputs are collected into a `Vec`
+///   instead of a single value, for a `tag` field they are discarded.
+fn field_parser(field: &syn::Field) -> ParsedField {
+  let ident = field.ident.clone().unwrap();
+  let attr = field.attrs.iter()
+    .find(|a| a.name() == "parse")
+    .unwrap_or_else(|| panic!("field `{}` is missing a #[parse(..)] attribute", ident));
+
+  let mut tag = None;
+  let mut with = None;
+  let mut many0 = false;
+
+  if let syn::MetaItem::List(_, ref items) = attr.value {
+    for item in items {
+      match *item {
+        syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref key, syn::Lit::Str(ref value, _))) => {
+          match key.as_ref() {
+            "tag" => tag = Some(value.clone()),
+            "with" => with = Some(value.clone()),
+            other => panic!("unknown #[parse(..)] key `{}`", other),
+          }
+        }
+        syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref key)) if key == "many0" => {
+          many0 = true;
+        }
+        _ => panic!("unsupported #[parse(..)] attribute shape"),
+      }
+    }
+  }
+
+  let (inner, captured) = if let Some(with) = with {
+    let with = Ident::new(with);
+    (quote! { call!(#with) }, true)
+  } else if let Some(tag) = tag {
+    (quote! { tag!(#tag) }, false)
+  } else {
+    panic!("#[parse(..)] needs either `tag = \"..\"` or `with = \"..\"`");
+  };
+
+  let parser = if many0 {
+    quote! { many0!(#inner) }
+  } else {
+    inner
+  };
+
+  ParsedField { ident: ident, parser: parser, captured: captured }
+}
+
+fn to_snake_case(name: &str) -> String {
+  let mut out = String::new();
+  for (i, c) in name.chars().enumerate() {
+    if c.is_uppercase() {
+      if i > 0 {
+        out.push('_');
+      }
+      out.extend(c.to_lowercase());
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
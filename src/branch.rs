@@ -0,0 +1,63 @@
+//! `alt!`: try a `|`-separated list of sub-parsers in order, returning
+//! the first one that succeeds.
+//!
+//! Under `verbose_errors`, if every branch fails, their `Error`s are
+//! combined with [`Error::combine`](::verbose_errors::Error::combine)
+//! instead of only the last branch's error surviving, so a grammar
+//! failure reports every alternative that was tried, not just the one
+//! tried last. Under `simple_errors`, `ErrorKind` has nothing to
+//! combine into, so `alt!` keeps the traditional "report the last
+//! branch's error" behaviour.
+//!
+//! (See [`function`](::function) for why this isn't built as a thin
+//! wrapper over `function::alt`, despite doing the same thing: wrapping
+//! arbitrary sub-parsers in closures and handing them to a generic
+//! function runs into a closure type-inference wall that a directly
+//! recursive macro doesn't.)
+
+#[cfg(feature = "verbose-errors")]
+#[macro_export]
+macro_rules! alt (
+  ($i:expr, $e:ident) => (
+    alt!($i, call!($e));
+  );
+  ($i:expr, $submac:ident!( $($args:tt)* )) => (
+    $submac!($i, $($args)*)
+  );
+  ($i:expr, $e:ident | $($rest:tt)*) => (
+    alt!($i, call!($e) | $($rest)*)
+  );
+  ($i:expr, $submac:ident!( $($args:tt)* ) | $($rest:tt)*) => (
+    match $submac!($i.clone(), $($args)*) {
+      $crate::IResult::Done(i, o) => $crate::IResult::Done(i, o),
+      $crate::IResult::Incomplete(n) => $crate::IResult::Incomplete(n),
+      $crate::IResult::Error(e1) => {
+        match alt!($i, $($rest)*) {
+          $crate::IResult::Error(e2) => $crate::IResult::Error(e1.combine(e2)),
+          other => other,
+        }
+      }
+    }
+  );
+);
+
+#[cfg(not(feature = "verbose-errors"))]
+#[macro_export]
+macro_rules! alt (
+  ($i:expr, $e:ident) => (
+    alt!($i, call!($e));
+  );
+  ($i:expr, $submac:ident!( $($args:tt)* )) => (
+    $submac!($i, $($args)*)
+  );
+  ($i:expr, $e:ident | $($rest:tt)*) => (
+    alt!($i, call!($e) | $($rest)*)
+  );
+  ($i:expr, $submac:ident!( $($args:tt)* ) | $($rest:tt)*) => (
+    match $submac!($i.clone(), $($args)*) {
+      $crate::IResult::Done(i, o) => $crate::IResult::Done(i, o),
+      $crate::IResult::Incomplete(n) => $crate::IResult::Incomplete(n),
+      $crate::IResult::Error(_) => alt!($i, $($rest)*),
+    }
+  );
+);
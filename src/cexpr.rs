@@ -0,0 +1,302 @@
+//! C preprocessor constant expression parsing, behind the `cexpr`
+//! feature.
+//!
+//! `cexpr` (used in turn by `bindgen`) needs to evaluate the constant
+//! expressions that show up in `#define` and enum values: integer and
+//! char literals (string literals are not parsed) combined with the full
+//! C operator-precedence expression grammar (`<<`, `>>`, the bitwise and logical operators,
+//! comparisons, and `?:`). Rather than leaving every downstream crate to
+//! reimplement precedence climbing on top of `chain!`, this module keeps
+//! a maintained copy in tree, built the same way the numeric literal
+//! parsers and the calculator in the crate documentation are: a chain of
+//! `named!` parsers, one per precedence level, each folding its
+//! left-associative operators with `chain!`/`tap!`. `multiplicative_expr`
+//! folds its operators by hand instead of through `left_assoc_level!`,
+//! since `/` and `%` need to reject a zero divisor rather than letting
+//! it panic.
+//!
+//! Only the subset of the grammar needed to *evaluate* a constant
+//! expression is implemented - there is no AST, just a [`CValue`]
+//! carrying the result of each sub-expression.
+
+use std::str;
+use character::{digit, hex_digit, oct_digit};
+
+/// The result of evaluating a C constant expression.
+///
+/// C promotes everything that isn't already wider than `int` up to
+/// `int`/`unsigned int` for arithmetic, so a char literal or the result
+/// of a comparison is represented the same way an integer literal is.
+/// A decimal literal is an `Int` unless it has a `U` suffix; a hex
+/// literal, and an octal literal other than a lone `0`, is always a
+/// `UInt`. `*`, `/`, `%`, `+` and `-` yield a `UInt` when either operand
+/// is one; every other operator yields an `Int`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CValue {
+  Int(i64),
+  UInt(u64),
+}
+
+impl CValue {
+  fn as_i64(self) -> i64 {
+    match self {
+      CValue::Int(i) => i,
+      CValue::UInt(u) => u as i64,
+    }
+  }
+
+  fn is_unsigned(self) -> bool {
+    match self {
+      CValue::UInt(_) => true,
+      CValue::Int(_) => false,
+    }
+  }
+
+  fn unsigned_if(self, other: CValue) -> bool {
+    self.is_unsigned() || other.is_unsigned()
+  }
+}
+
+named!(int_suffix, recognize!(many0!(alt!(tag!("u") | tag!("U") | tag!("l") | tag!("L")))));
+
+named!(hex_literal<CValue>,
+  chain!(
+    tag!("0") ~
+    alt!(tag!("x") | tag!("X")) ~
+    digits: hex_digit ~
+    int_suffix,
+    || { CValue::UInt(u64::from_str_radix(str::from_utf8(digits).unwrap(), 16).unwrap()) }
+  )
+);
+
+named!(octal_literal<CValue>,
+  chain!(
+    tag!("0") ~
+    digits: oct_digit ~
+    int_suffix,
+    || {
+      if digits.is_empty() {
+        CValue::Int(0)
+      } else {
+        CValue::UInt(u64::from_str_radix(str::from_utf8(digits).unwrap(), 8).unwrap())
+      }
+    }
+  )
+);
+
+named!(decimal_literal<CValue>,
+  chain!(
+    digits: digit ~
+    suffix: int_suffix,
+    || {
+      let value = str::from_utf8(digits).unwrap().parse::<u64>().unwrap();
+      if suffix.iter().any(|&b| b == b'u' || b == b'U') {
+        CValue::UInt(value)
+      } else {
+        CValue::Int(value as i64)
+      }
+    }
+  )
+);
+
+/// A single character escape, as it would appear after the backslash in
+/// `'\n'` or `"a\tb"`: `\n`, `\t`, `\r`, `\0`, `\\`, `\'`, `\"`, or `\xNN`.
+named!(char_escape<u8>,
+  preceded!(tag!("\\"),
+    alt!(
+      tag!("n")  => { |_| b'\n' }
+    | tag!("t")  => { |_| b'\t' }
+    | tag!("r")  => { |_| b'\r' }
+    | tag!("0")  => { |_| b'\0' }
+    | tag!("\\") => { |_| b'\\' }
+    | tag!("'")  => { |_| b'\'' }
+    | tag!("\"") => { |_| b'"'  }
+    | preceded!(tag!("x"), hex_digit) => {
+        |digits: &[u8]| u8::from_str_radix(str::from_utf8(digits).unwrap(), 16).unwrap()
+      }
+    )
+  )
+);
+
+named!(char_literal<CValue>,
+  delimited!(
+    tag!("'"),
+    alt!(char_escape | take!(1) => { |s: &[u8]| s[0] }),
+    tag!("'")
+  ) => { |c: u8| CValue::Int(c as i64) }
+);
+
+/// Any of the literal forms a primary expression can bottom out on.
+named!(integer_literal<CValue>, alt!(hex_literal | octal_literal | decimal_literal | char_literal));
+
+named!(primary_expr<CValue>,
+  alt!(
+    integer_literal
+  | delimited!(tag!("("), ws!(expr), tag!(")"))
+  )
+);
+
+named!(unary_expr<CValue>,
+  alt!(
+    preceded!(tag!("-"), ws!(unary_expr)) => { |v: CValue| CValue::Int(-v.as_i64()) }
+  | preceded!(tag!("~"), ws!(unary_expr)) => { |v: CValue| CValue::Int(!v.as_i64()) }
+  | preceded!(tag!("!"), ws!(unary_expr)) => { |v: CValue| CValue::Int((v.as_i64() == 0) as i64) }
+  | primary_expr
+  )
+);
+
+macro_rules! left_assoc_level (
+  ($name:ident, $next:ident, $($op:expr => $f:expr),+) => (
+    named!($name<CValue>,
+      chain!(
+        mut acc: $next ~
+        many0!(
+          alt!(
+            $(tap!(rhs: preceded!(ws!(tag!($op)), ws!($next)) => { acc = ($f)(acc, rhs); }))+
+          )
+        ),
+        || { acc }
+      )
+    );
+  );
+);
+
+// Not built with `left_assoc_level!`: `/` and `%` need to reject a zero
+// divisor instead of letting the native `i64`/`u64` division panic, and
+// the closures `left_assoc_level!` folds with have no way to signal that
+// back to the `chain!` around them - only a `return` written directly in
+// the `tap!` action block (inlined into this function by `named!`, same
+// as the early returns `many0!`/`tag!` use for `Incomplete`) can.
+named!(multiplicative_expr<CValue>,
+  chain!(
+    mut acc: unary_expr ~
+    many0!(
+      alt!(
+        tap!(rhs: preceded!(ws!(tag!("*")), ws!(unary_expr)) => {
+          acc = if acc.unsigned_if(rhs) { CValue::UInt((acc.as_i64() * rhs.as_i64()) as u64) } else { CValue::Int(acc.as_i64() * rhs.as_i64()) };
+        }) |
+        tap!(rhs: preceded!(ws!(tag!("/")), ws!(unary_expr)) => {
+          if rhs.as_i64() == 0 {
+            return $crate::IResult::Error(error_position!(ErrorKind::DivisionByZero, i));
+          }
+          acc = if acc.unsigned_if(rhs) { CValue::UInt((acc.as_i64() / rhs.as_i64()) as u64) } else { CValue::Int(acc.as_i64() / rhs.as_i64()) };
+        }) |
+        tap!(rhs: preceded!(ws!(tag!("%")), ws!(unary_expr)) => {
+          if rhs.as_i64() == 0 {
+            return $crate::IResult::Error(error_position!(ErrorKind::DivisionByZero, i));
+          }
+          acc = if acc.unsigned_if(rhs) { CValue::UInt((acc.as_i64() % rhs.as_i64()) as u64) } else { CValue::Int(acc.as_i64() % rhs.as_i64()) };
+        })
+      )
+    ),
+    || { acc }
+  )
+);
+
+left_assoc_level!(additive_expr, multiplicative_expr,
+  "+" => |a: CValue, b: CValue| if a.unsigned_if(b) { CValue::UInt((a.as_i64() + b.as_i64()) as u64) } else { CValue::Int(a.as_i64() + b.as_i64()) },
+  "-" => |a: CValue, b: CValue| if a.unsigned_if(b) { CValue::UInt((a.as_i64() - b.as_i64()) as u64) } else { CValue::Int(a.as_i64() - b.as_i64()) }
+);
+
+left_assoc_level!(shift_expr, additive_expr,
+  "<<" => |a: CValue, b: CValue| CValue::Int(a.as_i64() << b.as_i64()),
+  ">>" => |a: CValue, b: CValue| CValue::Int(a.as_i64() >> b.as_i64())
+);
+
+left_assoc_level!(relational_expr, shift_expr,
+  "<=" => |a: CValue, b: CValue| CValue::Int((a.as_i64() <= b.as_i64()) as i64),
+  ">=" => |a: CValue, b: CValue| CValue::Int((a.as_i64() >= b.as_i64()) as i64),
+  "<" => |a: CValue, b: CValue| CValue::Int((a.as_i64() < b.as_i64()) as i64),
+  ">" => |a: CValue, b: CValue| CValue::Int((a.as_i64() > b.as_i64()) as i64)
+);
+
+left_assoc_level!(equality_expr, relational_expr,
+  "==" => |a: CValue, b: CValue| CValue::Int((a.as_i64() == b.as_i64()) as i64),
+  "!=" => |a: CValue, b: CValue| CValue::Int((a.as_i64() != b.as_i64()) as i64)
+);
+
+left_assoc_level!(bitand_expr, equality_expr, "&" => |a: CValue, b: CValue| CValue::Int(a.as_i64() & b.as_i64()));
+left_assoc_level!(bitxor_expr, bitand_expr, "^" => |a: CValue, b: CValue| CValue::Int(a.as_i64() ^ b.as_i64()));
+left_assoc_level!(bitor_expr, bitxor_expr, "|" => |a: CValue, b: CValue| CValue::Int(a.as_i64() | b.as_i64()));
+
+left_assoc_level!(logand_expr, bitor_expr,
+  "&&" => |a: CValue, b: CValue| CValue::Int((a.as_i64() != 0 && b.as_i64() != 0) as i64)
+);
+left_assoc_level!(logor_expr, logand_expr,
+  "||" => |a: CValue, b: CValue| CValue::Int((a.as_i64() != 0 || b.as_i64() != 0) as i64)
+);
+
+named!(ternary_expr<CValue>,
+  chain!(
+    cond: logor_expr ~
+    rest: opt!(
+      chain!(
+        ws!(tag!("?")) ~
+        if_true: ws!(expr) ~
+        ws!(tag!(":")) ~
+        if_false: ws!(ternary_expr),
+        || { (if_true, if_false) }
+      )
+    ),
+    || {
+      match rest {
+        Some((if_true, if_false)) => if cond.as_i64() != 0 { if_true } else { if_false },
+        None => cond,
+      }
+    }
+  )
+);
+
+/// Parses and evaluates a full C constant expression, with the usual
+/// precedence: `?:` loosest, then `||`, `&&`, `|`, `^`, `&`, `==`/`!=`,
+/// relational, `<<`/`>>`, `+`/`-`, `*`/`/`/`%`, unary, down to literals
+/// and parenthesized sub-expressions.
+named!(pub expr<CValue>, ws!(ternary_expr));
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_decimal_integer() {
+    assert_eq!(expr(b"42"), ::IResult::Done(&b""[..], CValue::Int(42)));
+  }
+
+  #[test]
+  fn parses_hex_integer() {
+    assert_eq!(expr(b"0x2A"), ::IResult::Done(&b""[..], CValue::UInt(42)));
+  }
+
+  #[test]
+  fn parses_octal_integer() {
+    assert_eq!(expr(b"052"), ::IResult::Done(&b""[..], CValue::UInt(42)));
+  }
+
+  #[test]
+  fn parses_char_literal_with_escape() {
+    assert_eq!(expr(b"'\\n'"), ::IResult::Done(&b""[..], CValue::Int(b'\n' as i64)));
+  }
+
+  #[test]
+  fn division_by_zero_is_a_parse_error_not_a_panic() {
+    assert!(expr(b"1/0").is_err());
+    assert!(expr(b"1%0").is_err());
+  }
+
+  #[test]
+  fn respects_operator_precedence() {
+    assert_eq!(expr(b"1+2*3"), ::IResult::Done(&b""[..], CValue::Int(7)));
+    assert_eq!(expr(b"(1+2)*3"), ::IResult::Done(&b""[..], CValue::Int(9)));
+  }
+
+  #[test]
+  fn evaluates_shift_and_bitwise() {
+    assert_eq!(expr(b"1<<4 | 1"), ::IResult::Done(&b""[..], CValue::Int(17)));
+  }
+
+  #[test]
+  fn evaluates_ternary() {
+    assert_eq!(expr(b"1 ? 2 : 3"), ::IResult::Done(&b""[..], CValue::Int(2)));
+    assert_eq!(expr(b"0 ? 2 : 3"), ::IResult::Done(&b""[..], CValue::Int(3)));
+  }
+}
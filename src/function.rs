@@ -0,0 +1,255 @@
+//! Function-based combinators.
+//!
+//! Every combinator elsewhere in this crate (`alt!`, `delimited!`, `many0!`,
+//! `tag!`, ...) is a `macro_rules!` macro. That reads well for small
+//! grammars, but it means parsers can't be built up with ordinary Rust
+//! function composition, can't easily close over environment state, and
+//! fall afoul of the usual macro-hygiene surprises.
+//!
+//! This module offers the same handful of combinators as plain functions
+//! returning closures, generic over any input implementing [`Input`]
+//! instead of being hardcoded to `&[u8]`, with the exception of [`tag`],
+//! which matches a byte string against `&[u8]` input only; nothing here
+//! changes the shape of `IResult`.
+//!
+//! `branch::alt!` applies the same error-combining idea `alt()` below
+//! does, but isn't literally built on top of it (and neither are
+//! `multi::many0!`/`many1!`, which have no errors to combine): wrapping
+//! a macro's sub-parser invocations in closures
+//! and feeding them to a generic function taking `impl Fn(I) -> ..`
+//! defeats Rust's closure-argument type inference (the closure's `I` has
+//! nothing concrete to unify against until the function is already
+//! called), so the macros keep their own small, directly-recursive
+//! implementations instead. `named!`/`chain!` are unrelated to this
+//! module entirely - `chain!`'s arbitrary per-field sequencing doesn't
+//! correspond to any single fixed-arity combinator here.
+//!
+//! ```
+//! # #[macro_use] extern crate nom;
+//! # use nom::IResult;
+//! # use nom::function::{tag, preceded};
+//! # fn main() {
+//! let parser = preceded(tag(b"("), tag(b"abc"));
+//! assert_eq!(parser(&b"(abc)"[..]), IResult::Done(&b")"[..], &b"abc"[..]));
+//! # }
+//! ```
+
+use internal::{IResult, Needed};
+use traits::Input;
+use {Error, ErrorKind};
+
+/// Returns a parser that recognizes the literal byte string `t` at the
+/// beginning of its input.
+///
+/// Equivalent to `tag!(t)`, but as a plain function that can be stored,
+/// passed around, and composed with the other combinators in this module.
+pub fn tag<'a>(t: &'static [u8]) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8]> {
+  move |input: &'a [u8]| {
+    let len = t.len();
+
+    if input.input_len() < len {
+      return IResult::Incomplete(Needed::Size(len));
+    }
+
+    let (consumed, rest) = input.split_at(len);
+    if consumed == t {
+      IResult::Done(rest, consumed)
+    } else {
+      IResult::Error(error_position!(ErrorKind::Tag, input))
+    }
+  }
+}
+
+/// Returns a parser that runs `second` after `first` succeeds, discarding
+/// the output of `first` and keeping the output of `second`.
+///
+/// Equivalent to `preceded!(first, second)`.
+pub fn preceded<I, O1, O2, F, G>(first: F, second: G) -> impl Fn(I) -> IResult<I, O2>
+  where I: Input,
+        F: Fn(I) -> IResult<I, O1>,
+        G: Fn(I) -> IResult<I, O2> {
+  move |input: I| {
+    match first(input) {
+      IResult::Done(rest, _) => second(rest),
+      IResult::Error(e) => IResult::Error(e),
+      IResult::Incomplete(n) => IResult::Incomplete(n),
+    }
+  }
+}
+
+/// Returns a parser that runs `opening`, then `inner`, then `closing`,
+/// keeping only the output of `inner`.
+///
+/// Equivalent to `delimited!(opening, inner, closing)`.
+pub fn delimited<I, O1, O2, O3, F, G, H>(opening: F, inner: G, closing: H) -> impl Fn(I) -> IResult<I, O2>
+  where I: Input,
+        F: Fn(I) -> IResult<I, O1>,
+        G: Fn(I) -> IResult<I, O2>,
+        H: Fn(I) -> IResult<I, O3> {
+  move |input: I| {
+    let (rest, _) = try_parse!(input, opening);
+    let (rest, o2) = try_parse!(rest, inner);
+    let (rest, _) = try_parse!(rest, closing);
+    IResult::Done(rest, o2)
+  }
+}
+
+/// Returns a parser that tries each parser in the tuple `parsers` in
+/// order, returning the result of the first one that succeeds.
+///
+/// Equivalent to `alt!(a | b | c)`, except the alternatives are passed as
+/// a tuple rather than separated by `|`.
+pub fn alt<I, O, List: Alt<I, O>>(parsers: List) -> impl Fn(I) -> IResult<I, O>
+  where I: Input {
+  move |input: I| parsers.parse_alt(input)
+}
+
+/// Implemented for tuples of parsers sharing the same input and output
+/// type, so [`alt`] can be called with `(p1, p2, ..)` the same way
+/// `alt!` is called with `p1 | p2 | ..`.
+pub trait Alt<I, O> {
+  /// Tries every parser in the tuple in order and returns the first
+  /// success. If every parser fails, the errors of every failed branch
+  /// are combined, so the caller can see everything that was tried
+  /// instead of just the last alternative.
+  fn parse_alt(&self, input: I) -> IResult<I, O>;
+}
+
+macro_rules! alt_trait_impl {
+  ($($parser:ident)+) => (
+    impl<I: Input + Clone, O, $($parser: Fn(I) -> IResult<I, O>),+> Alt<I, O> for ($($parser),+,) {
+      #[allow(non_snake_case)]
+      fn parse_alt(&self, input: I) -> IResult<I, O> {
+        let ($(ref $parser),+,) = *self;
+        let mut err: Option<Error<I>> = None;
+        $(
+          match $parser(input.clone()) {
+            IResult::Done(rest, o) => return IResult::Done(rest, o),
+            IResult::Error(e) => {
+              err = Some(match err {
+                Some(acc) => acc.combine(e),
+                None => e,
+              });
+            }
+            IResult::Incomplete(n) => return IResult::Incomplete(n),
+          }
+        )+
+        IResult::Error(err.expect("alt() is never called with zero alternatives"))
+      }
+    }
+  );
+}
+
+alt_trait_impl!(A);
+alt_trait_impl!(A B);
+alt_trait_impl!(A B C);
+alt_trait_impl!(A B C D);
+alt_trait_impl!(A B C D E);
+alt_trait_impl!(A B C D E F);
+
+/// Returns a parser that applies `parser` as many times as possible
+/// (including zero), collecting the outputs into a `Vec`.
+///
+/// Equivalent to `many0!(parser)`.
+pub fn many0<I, O, F>(parser: F) -> impl Fn(I) -> IResult<I, Vec<O>>
+  where I: Input + Clone,
+        F: Fn(I) -> IResult<I, O> {
+  move |input: I| {
+    let mut acc = Vec::new();
+    let mut rest = input;
+
+    loop {
+      // nothing left to match: stop here rather than reporting the
+      // sub-parser's `Incomplete` on empty input.
+      if rest.input_len() == 0 {
+        return IResult::Done(rest, acc);
+      }
+
+      match parser(rest.clone()) {
+        IResult::Done(tail, o) => {
+          // an empty match would loop forever; stop as soon as no
+          // progress is made, same as `many0!`.
+          if tail.input_len() == rest.input_len() {
+            return IResult::Done(rest, acc);
+          }
+          acc.push(o);
+          rest = tail;
+        }
+        IResult::Error(_) => return IResult::Done(rest, acc),
+        IResult::Incomplete(n) => return IResult::Incomplete(n),
+      }
+    }
+  }
+}
+
+/// Returns a parser that runs `parser` and applies `f` to its output.
+///
+/// Equivalent to `map!(parser, f)`.
+pub fn map<I, O1, O2, F, G>(parser: F, f: G) -> impl Fn(I) -> IResult<I, O2>
+  where F: Fn(I) -> IResult<I, O1>,
+        G: Fn(O1) -> O2 {
+  move |input: I| {
+    match parser(input) {
+      IResult::Done(rest, o) => IResult::Done(rest, f(o)),
+      IResult::Error(e) => IResult::Error(e),
+      IResult::Incomplete(n) => IResult::Incomplete(n),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use internal::IResult;
+
+  #[test]
+  fn tag_matches_prefix() {
+    let parser = tag(b"abc");
+    assert_eq!(parser(&b"abcdef"[..]), IResult::Done(&b"def"[..], &b"abc"[..]));
+  }
+
+  #[test]
+  fn tag_rejects_mismatch() {
+    let parser = tag(b"abc");
+    assert!(parser(&b"xyz"[..]).is_err());
+  }
+
+  #[test]
+  fn preceded_keeps_second_output() {
+    let parser = preceded(tag(b"("), tag(b"abc"));
+    assert_eq!(parser(&b"(abc)"[..]), IResult::Done(&b")"[..], &b"abc"[..]));
+  }
+
+  #[test]
+  fn delimited_keeps_inner_output() {
+    let parser = delimited(tag(b"("), tag(b"abc"), tag(b")"));
+    assert_eq!(parser(&b"(abc)"[..]), IResult::Done(&b""[..], &b"abc"[..]));
+  }
+
+  #[test]
+  fn alt_tries_in_order() {
+    let parser = alt((tag(b"abc"), tag(b"def")));
+    assert_eq!(parser(&b"def"[..]), IResult::Done(&b""[..], &b"def"[..]));
+  }
+
+  #[test]
+  fn alt_combines_errors_of_every_failed_branch() {
+    let parser = alt((tag(b"abc"), tag(b"def")));
+    match parser(&b"xyz"[..]) {
+      IResult::Error(e) => assert_eq!(e.errors().len(), 2),
+      other => panic!("expected an Error, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn many0_collects_matches() {
+    let parser = many0(tag(b"ab"));
+    assert_eq!(parser(&b"ababab"[..]), IResult::Done(&b""[..], vec![&b"ab"[..], &b"ab"[..], &b"ab"[..]]));
+  }
+
+  #[test]
+  fn map_transforms_output() {
+    let parser = map(tag(b"abc"), |o: &[u8]| o.len());
+    assert_eq!(parser(&b"abc"[..]), IResult::Done(&b""[..], 3));
+  }
+}
@@ -151,6 +151,12 @@ pub use self::stream::*;
 #[cfg(not(feature = "core"))]
 pub use self::str::*;
 
+#[cfg(feature = "locate")]
+pub use self::span::*;
+
+#[cfg(feature = "cexpr")]
+pub use self::cexpr::*;
+
 #[macro_use] mod util;
 mod traits;
 
@@ -174,6 +180,9 @@ mod traits;
 #[cfg(not(feature = "core"))]
 pub mod whitespace;
 
+#[cfg(feature = "cexpr")]
+#[macro_use] pub mod cexpr;
+
 #[cfg(feature = "regexp")]
 #[macro_use] mod regexp;
 
@@ -184,3 +193,12 @@ mod stream;
 
 #[cfg(not(feature = "core"))]
 mod str;
+
+// `function::Alt`'s implementation requires `Error::combine`, which only
+// `verbose_errors` provides (`simple_errors`'s `ErrorKind` has nothing to
+// combine into) - so the whole module is gated the same way.
+#[cfg(feature = "verbose-errors")]
+pub mod function;
+
+#[cfg(feature = "locate")]
+pub mod span;
@@ -0,0 +1,77 @@
+//! `many0!`/`many1!`: apply a sub-parser as many times as possible,
+//! collecting the outputs into a `Vec`.
+//!
+//! Neither macro needs [`Error::combine`](::verbose_errors::Error::combine):
+//! unlike `alt!`, they never try more than one sub-parser at a given
+//! position, so there's only ever the single failed attempt that ends
+//! the loop - nothing to combine it with.
+
+#[macro_export]
+macro_rules! many0 (
+  ($i:expr, $submac:ident!( $($args:tt)* )) => (
+    {
+      let mut acc = ::std::vec::Vec::new();
+      let mut input = $i.clone();
+
+      loop {
+        // nothing left to match: stop here rather than reporting the
+        // sub-parser's `Incomplete` on empty input.
+        if $crate::traits::Input::input_len(&input) == 0 {
+          break;
+        }
+
+        match $submac!(input.clone(), $($args)*) {
+          $crate::IResult::Done(rest, o) => {
+            // stop as soon as a match consumes nothing, or this would
+            // loop forever.
+            if $crate::traits::Input::input_len(&rest) == $crate::traits::Input::input_len(&input) {
+              break;
+            }
+            acc.push(o);
+            input = rest;
+          }
+          $crate::IResult::Error(_) => break,
+          $crate::IResult::Incomplete(n) => return $crate::IResult::Incomplete(n),
+        }
+      }
+
+      $crate::IResult::Done(input, acc)
+    }
+  );
+  ($i:expr, $f:expr) => (
+    many0!($i, call!($f));
+  );
+);
+
+#[macro_export]
+macro_rules! many1 (
+  ($i:expr, $submac:ident!( $($args:tt)* )) => (
+    match $submac!($i.clone(), $($args)*) {
+      $crate::IResult::Error(e) => $crate::IResult::Error(e),
+      $crate::IResult::Incomplete(n) => $crate::IResult::Incomplete(n),
+      $crate::IResult::Done(i1, o1) => {
+        let mut acc = vec![o1];
+        let mut input = i1;
+
+        loop {
+          match $submac!(input.clone(), $($args)*) {
+            $crate::IResult::Done(rest, o) => {
+              if $crate::traits::Input::input_len(&rest) == $crate::traits::Input::input_len(&input) {
+                break;
+              }
+              acc.push(o);
+              input = rest;
+            }
+            $crate::IResult::Error(_) => break,
+            $crate::IResult::Incomplete(n) => return $crate::IResult::Incomplete(n),
+          }
+        }
+
+        $crate::IResult::Done(input, acc)
+      }
+    }
+  );
+  ($i:expr, $f:expr) => (
+    many1!($i, call!($f));
+  );
+);
@@ -0,0 +1,149 @@
+//! Line and column tracking for combinator input, behind the `locate`
+//! feature.
+//!
+//! Every combinator in this crate hands back `&[u8]`/`&str` slices on
+//! failure, so a caller has no way to tell a user *where* in their
+//! source a parse went wrong short of re-scanning the original buffer
+//! for the offset themselves.
+//!
+//! [`LocatedSpan`] wraps an input and carries its byte offset into the
+//! original buffer alongside it. Combinators don't need to know it
+//! exists: it implements the same [`Input`](::traits::Input) trait the
+//! function combinators are generic over, so `preceded`, `delimited`,
+//! `alt`, `many0` and `map` work on `LocatedSpan<&[u8]>` exactly as they
+//! do on a plain `&[u8]`. The `tag` function and the macro combinators
+//! (`tag!`, `take!`, ...) take `&[u8]`/`&str` input and do not accept a
+//! `LocatedSpan`.
+//! When a parse fails, the remaining `LocatedSpan` can be asked for its
+//! `.offset()`, `.line()`, and `.column()`.
+//!
+//! Line numbers are updated incrementally as input is consumed (each
+//! split counts the newlines in the consumed chunk), so the hot path of
+//! advancing through input stays cheap. Column numbers are *not* tracked
+//! eagerly; `.column()` scans backward from the current offset to the
+//! previous newline on demand, since most consumed chunks are never
+//! asked for their column.
+
+use traits::{AsBytes, Input};
+
+/// An input wrapped with its position in the original buffer.
+///
+/// `T` is typically `&[u8]` or `&str`; both already implement the
+/// [`Input`](::traits::Input) and [`AsBytes`](::traits::AsBytes) traits
+/// `LocatedSpan` needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocatedSpan<T> {
+  fragment: T,
+  offset: usize,
+  line: u32,
+  original: T,
+}
+
+impl<T: AsBytes + Copy> LocatedSpan<T> {
+  /// Wraps `input` as the start of a new span: offset 0, line 1.
+  pub fn new(input: T) -> LocatedSpan<T> {
+    LocatedSpan {
+      fragment: input,
+      offset: 0,
+      line: 1,
+      original: input,
+    }
+  }
+
+  /// The part of the input this span currently points at.
+  pub fn fragment(&self) -> T {
+    self.fragment
+  }
+
+  /// How many bytes of the original input were consumed to reach here.
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  /// The 1-indexed line this span starts on.
+  pub fn line(&self) -> u32 {
+    self.line
+  }
+
+  /// The 1-indexed column this span starts on.
+  ///
+  /// Computed by scanning backward through the original input to the
+  /// previous `\n` (or the start of input), so it costs nothing unless
+  /// it's actually asked for.
+  pub fn column(&self) -> usize {
+    let original = self.original.as_bytes();
+    let mut column = 1;
+    let mut i = self.offset;
+
+    while i > 0 {
+      i -= 1;
+      if original[i] == b'\n' {
+        break;
+      }
+      column += 1;
+    }
+
+    column
+  }
+}
+
+impl<T: Input + AsBytes + Copy> Input for LocatedSpan<T> {
+  fn input_len(&self) -> usize {
+    self.fragment.input_len()
+  }
+
+  fn split_at(&self, count: usize) -> (Self, Self) {
+    let (consumed, rest) = self.fragment.split_at(count);
+    let newlines = consumed.as_bytes().iter().filter(|&&b| b == b'\n').count() as u32;
+
+    let consumed_span = LocatedSpan {
+      fragment: consumed,
+      offset: self.offset,
+      line: self.line,
+      original: self.original,
+    };
+
+    let rest_span = LocatedSpan {
+      fragment: rest,
+      offset: self.offset + count,
+      line: self.line + newlines,
+      original: self.original,
+    };
+
+    (consumed_span, rest_span)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn new_span_starts_at_line_one_column_one() {
+    let span = LocatedSpan::new(&b"abc"[..]);
+    assert_eq!(span.offset(), 0);
+    assert_eq!(span.line(), 1);
+    assert_eq!(span.column(), 1);
+  }
+
+  #[test]
+  fn split_at_tracks_offset_and_line() {
+    let span = LocatedSpan::new(&b"ab\ncd\nef"[..]);
+    let (_, rest) = span.split_at(3);
+    assert_eq!(rest.offset(), 3);
+    assert_eq!(rest.line(), 2);
+    assert_eq!(rest.column(), 1);
+
+    let (_, rest) = rest.split_at(3);
+    assert_eq!(rest.offset(), 6);
+    assert_eq!(rest.line(), 3);
+    assert_eq!(rest.column(), 1);
+  }
+
+  #[test]
+  fn column_counts_from_last_newline() {
+    let span = LocatedSpan::new(&b"ab\ncdef"[..]);
+    let (_, rest) = span.split_at(5);
+    assert_eq!(rest.column(), 3);
+  }
+}
@@ -0,0 +1,70 @@
+//! Traits abstracting over the input types combinators work on.
+//!
+//! The macro-based combinators in this crate (`tag!`, `take!`, etc.) are
+//! written directly against `&[u8]`/`&str`. The function-based combinators
+//! in `function` are written once, generically, against the small set of
+//! traits defined here, so the same `preceded`/`alt` functions work
+//! over any input type that implements them (byte slices, and wrappers
+//! such as `LocatedSpan`). The `tag` function is the exception: it only
+//! takes `&[u8]`.
+
+/// A minimal abstraction over parser input.
+///
+/// Implementors only need to know how to report their length and split
+/// themselves at a byte offset; the function combinators build everything
+/// else (`tag`, `preceded`, `many0`, ...) on top of these two operations.
+pub trait Input: Sized + Copy {
+  /// The number of elements left in this input.
+  fn input_len(&self) -> usize;
+
+  /// Splits the input at `count`, returning `(consumed, remainder)`.
+  ///
+  /// # Panics
+  ///
+  /// Implementations may panic if `count > self.input_len()`; callers in
+  /// this crate never call it with an out of bounds `count`.
+  fn split_at(&self, count: usize) -> (Self, Self);
+}
+
+impl<'a> Input for &'a [u8] {
+  fn input_len(&self) -> usize {
+    self.len()
+  }
+
+  fn split_at(&self, count: usize) -> (Self, Self) {
+    (&self[..count], &self[count..])
+  }
+}
+
+impl<'a> Input for &'a str {
+  fn input_len(&self) -> usize {
+    self.len()
+  }
+
+  fn split_at(&self, count: usize) -> (Self, Self) {
+    (&self[..count], &self[count..])
+  }
+}
+
+/// Exposes the raw bytes behind an input, regardless of whether it's a
+/// `&[u8]` or a `&str`.
+///
+/// `LocatedSpan` uses this to scan backward for the previous newline when
+/// computing a column number, without caring which flavour of input it
+/// was built from.
+pub trait AsBytes {
+  /// Returns the bytes making up this input.
+  fn as_bytes(&self) -> &[u8];
+}
+
+impl<'a> AsBytes for &'a [u8] {
+  fn as_bytes(&self) -> &[u8] {
+    self
+  }
+}
+
+impl<'a> AsBytes for &'a str {
+  fn as_bytes(&self) -> &[u8] {
+    str::as_bytes(self)
+  }
+}
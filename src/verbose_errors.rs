@@ -0,0 +1,128 @@
+//! Error management, `verbose_errors` flavour.
+//!
+//! The `simple_errors` feature represents a parse failure as a single
+//! `ErrorKind`: whichever branch failed last wins, and everything an
+//! `alt!` or `many*` tried before that is thrown away. That makes for a
+//! fast, small `IResult`, but a terrible diagnostic when a grammar with
+//! several alternatives fails: you only ever learn about the one
+//! alternative that happened to be tried last.
+//!
+//! This module keeps the same `Done`/`Error`/`Incomplete` shape for
+//! `IResult`, but gives the `Error` case a richer value: an ordered list
+//! of every `(position, ErrorKind)` pair that was recorded on the way to
+//! the failure, together with a [`Error::combine`] to merge two such
+//! lists. `alt!` uses `combine` to accumulate the errors of every
+//! exhausted branch instead of discarding all but the last one, so
+//! `Display` can report every expectation at once. The `many*`
+//! combinators only ever try one sub-parser at a position and do not
+//! call `combine`.
+
+use std::fmt;
+
+/// What kind of thing a combinator expected to find and didn't.
+///
+/// `Custom` carries whatever error type the grammar's author chose to
+/// return from `map_res!`, `add_error!`, and friends; the rest describe
+/// the built-in combinators.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind<E = u32> {
+  Custom(E),
+  Tag,
+  Alt,
+  Digit,
+  Many0,
+  Many1,
+  Count,
+  Eof,
+  /// A division or modulo whose divisor evaluated to zero.
+  DivisionByZero,
+}
+
+/// A parse error, carrying every `(position, ErrorKind)` pair recorded
+/// while trying to parse `P`-positioned input.
+///
+/// `P` is whatever the combinators use to identify "where" a failure
+/// happened - typically the remaining input at the point of failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error<P, E = u32> {
+  errors: Vec<(P, ErrorKind<E>)>,
+}
+
+impl<P, E> Error<P, E> {
+  /// Builds an error recording a single failure.
+  pub fn new(position: P, kind: ErrorKind<E>) -> Error<P, E> {
+    Error { errors: vec![(position, kind)] }
+  }
+
+  /// The `(position, ErrorKind)` pairs recorded so far, in the order
+  /// they were added.
+  pub fn errors(&self) -> &[(P, ErrorKind<E>)] {
+    &self.errors
+  }
+
+  /// Merges the failures recorded in `other` into `self`, keeping the
+  /// order both were recorded in.
+  ///
+  /// `alt!` calls this after every branch it tries fails, so by the
+  /// time all branches are exhausted the resulting `Error` lists what
+  /// every single one of them expected, not just the last.
+  pub fn combine(mut self, other: Error<P, E>) -> Error<P, E> {
+    self.errors.extend(other.errors);
+    self
+  }
+}
+
+impl<P: fmt::Display, E: fmt::Debug> fmt::Display for Error<P, E> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.errors.len() == 1 {
+      let (ref position, ref kind) = self.errors[0];
+      return write!(f, "expected {:?} at {}", kind, position);
+    }
+
+    write!(f, "expected one of:")?;
+    for &(ref position, ref kind) in &self.errors {
+      write!(f, "\n  {:?} at {}", kind, position)?;
+    }
+    Ok(())
+  }
+}
+
+/// Builds an `Error` recording a single failure at the current input
+/// position.
+///
+/// Used by combinators the same way `error_code!` is used under
+/// `simple_errors`: `error_position!(ErrorKind::Tag, input)`.
+#[macro_export]
+macro_rules! error_position(
+  ($code:expr, $input:expr) => (
+    $crate::Error::new($input, $code)
+  );
+);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn new_records_a_single_error() {
+    let e: Error<&str> = Error::new("abc", ErrorKind::Tag);
+    assert_eq!(e.errors(), &[("abc", ErrorKind::Tag)]);
+  }
+
+  #[test]
+  fn combine_keeps_both_in_order() {
+    let a: Error<&str> = Error::new("abc", ErrorKind::Tag);
+    let b: Error<&str> = Error::new("abc", ErrorKind::Digit);
+    let combined = a.combine(b);
+    assert_eq!(combined.errors(), &[("abc", ErrorKind::Tag), ("abc", ErrorKind::Digit)]);
+  }
+
+  #[test]
+  fn display_lists_every_expectation() {
+    let a: Error<&str> = Error::new("abc", ErrorKind::Tag);
+    let b: Error<&str> = Error::new("abc", ErrorKind::Digit);
+    let rendered = format!("{}", a.combine(b));
+    assert!(rendered.contains("Tag"));
+    assert!(rendered.contains("Digit"));
+  }
+}